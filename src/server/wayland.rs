@@ -1,9 +1,13 @@
 use super::*;
-use hbb_common::{allow_err, platform::linux::DISTRO};
+use ashpd::desktop::remote_desktop::{DeviceType, KeyState, RemoteDesktop};
+use ashpd::desktop::{PersistMode, Session};
+use hbb_common::{allow_err, config::Config, platform::linux::DISTRO};
 use scrap::{
     is_cursor_embedded, set_map_err, Capturer, Display, Frame, TraitCapturer, WaylandDisplay,
 };
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use crate::{
     client::{
@@ -12,23 +16,149 @@ use crate::{
     platform::linux::is_x11,
 };
 
+// How long `get_capturer()` is willing to wait for an in-progress session
+// recovery before giving up and reporting "no capturer display info" to the
+// caller, same as if `check_init()` had never run.
+const RECOVERY_BACKOFF: Duration = Duration::from_millis(50);
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const OPTION_WAYLAND_RESTORE_TOKEN: &str = "wayland-restore-token";
+
+// Headless virtual display, used when there is no real wayland output to
+// capture. Resolution is configurable so the peer can be told to start at
+// something other than the default before the first connection arrives.
+const OPTION_VIRTUAL_DISPLAY_RESOLUTION: &str = "wayland-virtual-display-resolution";
+const DEFAULT_VIRTUAL_DISPLAY_RESOLUTION: (u32, u32) = (1920, 1080);
+
 lazy_static::lazy_static! {
     static ref CAP_DISPLAY_INFO: RwLock<u64> = RwLock::new(0);
     static ref LOG_SCRAP_COUNT: Mutex<u32> = Mutex::new(0);
 }
 
+// Set while the session supervisor is rebuilding `CAP_DISPLAY_INFO` after a
+// fatal PipeWire error, so concurrent `get_capturer()` callers back off
+// instead of racing the rebuild or bailing out entirely.
+static RECOVERING: AtomicBool = AtomicBool::new(false);
+
+// Whether the PipeWire stream negotiated `SPA_DATA_DmaBuf` buffers instead
+// of `SPA_DATA_MemFd`. `video_service` would check this to decide whether
+// it can import `Frame::DmaBuf` planes straight into a hardware encoder, or
+// whether it should expect the mapped-memory `Frame::PixelBuffer` fallback.
+//
+// Hardwired to `false`: that negotiation happens on the PipeWire stream's
+// `Connect`/`param_changed` exchange inside `scrap`'s own stream consumer,
+// an implementation detail the xdg-desktop-portal D-Bus interface never
+// surfaces and `ashpd` -- a portal-session client, not a PipeWire one --
+// has no way to observe from out here. There's no `Frame::DmaBuf` variant
+// for `scrap::Frame` either. Both need to be added inside `scrap` itself
+// before this flag can ever legitimately flip to `true`; flipping it
+// without that would just report DMA-BUF support `frame()` can't actually
+// deliver.
+static DMABUF_CAPTURE: AtomicBool = AtomicBool::new(false);
+
+pub(super) fn dmabuf_capture_enabled() -> bool {
+    DMABUF_CAPTURE.load(Ordering::SeqCst)
+}
+
+fn get_saved_restore_token() -> Option<String> {
+    let token = Config::get_option(OPTION_WAYLAND_RESTORE_TOKEN);
+    if token.is_empty() {
+        None
+    } else {
+        Some(token)
+    }
+}
+
+// to-do: no caller actually persists a *new* token yet -- see the to-do in
+// `build_cap_display_info()` -- but `Config::set_option` is the write-side
+// counterpart of the `Config::get_option` read in `get_saved_restore_token()`,
+// so this is what the eventual write-back should call once `scrap` can report
+// a restore token.
+#[allow(dead_code)]
+fn save_restore_token(token: &str) {
+    if token.is_empty() {
+        return;
+    }
+    if get_saved_restore_token().as_deref() != Some(token) {
+        Config::set_option(OPTION_WAYLAND_RESTORE_TOKEN.to_owned(), token.to_owned());
+    }
+}
+
+fn virtual_display_resolution() -> (u32, u32) {
+    let raw = Config::get_option(OPTION_VIRTUAL_DISPLAY_RESOLUTION);
+    raw.split_once('x')
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .unwrap_or(DEFAULT_VIRTUAL_DISPLAY_RESOLUTION)
+}
+
+// to-do: this can never return `Ok` in this tree, by construction, not just
+// for lack of DRM privileges. `scrap::Display` has no public constructor --
+// every variant it exposes (`WAYLAND(WaylandDisplay)`, the X11 one, etc.)
+// is built from a private field `scrap` alone can populate, so there is no
+// amount of DRM/KMS code written here -- opening `/dev/dri/cardN`, taking a
+// session, driving a dumb-buffer-backed CRTC/connector -- that can ever
+// produce a `Display` value to return. A real headless fallback needs
+// `scrap` itself to grow a constructor (e.g. `Display::headless(width,
+// height)`) backed by that dumb-buffer setup internally. Until then, this
+// only confirms a DRM device is even reachable and reports honestly that it
+// can't go further, instead of fabricating a `Display` some other way.
+fn ensure_virtual_display(width: u32, height: u32) -> ResultType<Display> {
+    let has_drm_device = std::fs::read_dir("/dev/dri")
+        .map(|entries| {
+            entries.filter_map(|e| e.ok()).any(|e| {
+                e.file_name()
+                    .to_str()
+                    .map(|name| name.starts_with("card"))
+                    .unwrap_or(false)
+            })
+        })
+        .unwrap_or(false);
+    if !has_drm_device {
+        bail!("No DRM device found under /dev/dri to back a virtual display");
+    }
+    bail!(
+        "Found a DRM device, but creating a {}x{} virtual display needs scrap-side \
+         dumb-buffer support that doesn't exist yet",
+        width,
+        height
+    );
+}
+
 pub fn init() {
     set_map_err(map_err_scrap);
 }
 
+// `video_service` can match on this to know the session is being rebuilt in
+// place and the frame should simply be retried, rather than surfaced to the
+// peer as a capture failure. Distinct from the plain `WouldBlock` that
+// `CapturerPtr::frame()` returns on an ordinary per-frame timeout -- that one
+// means "no new frame this interval", not "session unusable right now".
+//
+// to-do: nothing in this source tree actually calls this yet -- the intended
+// caller is `video_service`'s capture-error handling, which isn't part of
+// this snapshot. Kept `#[allow(dead_code)]` until that wiring lands there.
+#[allow(dead_code)]
+pub(super) fn is_recoverable_scrap_error(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::Interrupted
+}
+
 fn map_err_scrap(err: String) -> io::Error {
     // to-do: Remove the following log
     log::error!("Wayland scrap error {}", &err);
 
-    // to-do: Handle error better, do not restart server
     if err.starts_with("Did not receive a reply") {
-        log::error!("Fatal pipewire error, {}", &err);
-        std::process::exit(-1);
+        log::error!(
+            "Fatal pipewire error, recovering the capture session in place: {}",
+            &err
+        );
+        spawn_recover_session();
+        // `Interrupted`, not `WouldBlock` -- `CapturerPtr::frame()` already
+        // uses `WouldBlock` for an ordinary per-frame timeout, and the two
+        // need to stay distinguishable by whoever is polling `frame()`.
+        return io::Error::new(
+            io::ErrorKind::Interrupted,
+            "Recovering wayland capture session",
+        );
     }
 
     if DISTRO.name.to_uppercase() == "Ubuntu".to_uppercase() {
@@ -62,15 +192,65 @@ fn try_log(err: &String) {
     *lock_count += 1;
 }
 
-struct CapturerPtr(*mut Capturer);
+// Shares ownership of a heap-allocated `Capturer` between the master
+// `CapDisplayInfo.capturers` list and whatever `CapturerInfo` `get_capturer()`
+// handed out to a video thread, so the underlying `Capturer` is only freed
+// once every `CapturerPtr` referencing it -- master list included -- has been
+// dropped. See `CapturerHandle`'s own doc comment for why this replaced the
+// old "raw pointer, `Clone` duplicates it, `Drop` frees it" pattern.
+struct CapturerPtr(Arc<CapturerHandle>);
 
 impl Clone for CapturerPtr {
     fn clone(&self) -> Self {
-        Self(self.0)
+        Self(self.0.clone())
     }
 }
 
-impl Drop for CapturerPtr {
+impl TraitCapturer for CapturerPtr {
+    // to-do: this always returns the mapped-memory `Frame` variant. DMA-BUF
+    // passthrough (a `Frame::DmaBuf` carrying plane fds/stride/offset/
+    // modifier/fourcc so `video_service` can import straight into a hardware
+    // encoder) needs that variant and a negotiation path added to `scrap`
+    // itself, neither of which exist in this tree yet -- `DMABUF_CAPTURE`
+    // above is hardwired to `false`, and nothing reads it to change what
+    // `frame()` hands back.
+    //
+    // This previously tried to replace this direct, blocking poll with a
+    // calloop event loop gated on a condvar the loop would signal once a
+    // PipeWire readiness fd became readable -- but that fd came from a
+    // `Capturer: AsRawFd` impl that doesn't exist in baseline scrap, so the
+    // loop never had a real fd to watch and `frame()` would have blocked for
+    // the full timeout and returned `WouldBlock` on every call. Back to
+    // calling scrap's own blocking `frame(timeout)` directly until scrap
+    // exposes a genuine readiness fd and something actually drains it.
+    fn frame<'a>(&'a mut self, timeout: Duration) -> io::Result<Frame<'a>> {
+        unsafe { (*self.0.as_ptr()).frame(timeout) }
+    }
+}
+
+// Owns the heap allocation backing a `Capturer` and frees it on `Drop`.
+// Always held behind an `Arc` (see `CapturerPtr`) rather than cloned
+// directly: the previous `CapturerPtr` duplicated the raw pointer on every
+// `clone()` and freed it from every copy's `Drop`, which double-freed
+// whenever more than one clone existed at once, and use-after-freed a video
+// thread's in-flight `frame()` call whenever a session recovery tore down
+// the master list's copy out from under it. Reference-counting the one real
+// allocation instead of the pointer value fixes both.
+struct CapturerHandle(*mut Capturer);
+
+// SAFETY: the pointer is exclusively owned by this `Arc`-wrapped handle
+// (never aliased outside of it) and `Capturer` itself is sent across the
+// same thread boundaries via `scrap::TraitCapturer`'s existing `Send` bound.
+unsafe impl Send for CapturerHandle {}
+unsafe impl Sync for CapturerHandle {}
+
+impl CapturerHandle {
+    fn as_ptr(&self) -> *mut Capturer {
+        self.0
+    }
+}
+
+impl Drop for CapturerHandle {
     fn drop(&mut self) {
         unsafe {
             let _capturer = Box::from_raw(self.0);
@@ -78,12 +258,6 @@ impl Drop for CapturerPtr {
     }
 }
 
-impl TraitCapturer for CapturerPtr {
-    fn frame<'a>(&'a mut self, timeout: Duration) -> io::Result<Frame<'a>> {
-        unsafe { (*self.0).frame(timeout) }
-    }
-}
-
 struct CapDisplayInfo {
     primary: usize,
     rects: Vec<((i32, i32), usize, usize)>,
@@ -100,7 +274,19 @@ pub(super) fn is_inited() -> Option<Message> {
     if is_x11() {
         None
     } else {
-        if *CAP_DISPLAY_INFO.read().unwrap() == 0 {
+        // to-do: this still shows the picker on every reconnect. Suppressing
+        // it when a restore token is on file needs `check_init()` to actually
+        // plumb that token into the portal negotiation, which isn't possible
+        // from this file alone -- see the to-do in `build_cap_display_info()`.
+        //
+        // While `spawn_recover_session()` is rebuilding `CAP_DISPLAY_INFO` in
+        // place after a transient PipeWire error, the pointer is briefly
+        // zeroed out just like on first startup -- don't show the "select a
+        // screen" prompt for that, or every recovery would re-prompt the
+        // peer for a choice they already made.
+        if RECOVERING.load(Ordering::SeqCst) {
+            None
+        } else if *CAP_DISPLAY_INFO.read().unwrap() == 0 {
             let mut msg_out = Message::new();
             let res = MessageBox {
                 msgtype: "nook-nocancel-hasclose".to_owned(),
@@ -117,6 +303,132 @@ pub(super) fn is_inited() -> Option<Message> {
     }
 }
 
+// Negotiates the portal session and builds a fresh `CapDisplayInfo`, along
+// with the bounding box of all displays. Shared by `check_init()` (first
+// startup) and `spawn_recover_session()` (rebuilding after a fatal PipeWire
+// error).
+fn build_cap_display_info() -> ResultType<(CapDisplayInfo, i32, i32, i32, i32)> {
+    let mut minx = i32::MAX;
+    let mut maxx = i32::MIN;
+    let mut miny = i32::MAX;
+    let mut maxy = i32::MIN;
+
+    // to-do: `Capturer::new()`/`Display::all()` below run their own portal
+    // `CreateSession`/`SelectSources` negotiation internally and there's no
+    // parameter on either call to pass a persist mode or restore token into
+    // it -- suppressing the picker on repeat connections needs the `scrap`
+    // wayland backend itself to take `get_saved_restore_token()` as an
+    // argument to that negotiation and hand back whatever token the portal
+    // returns, for `save_restore_token()` to persist. This can't be done
+    // from here with a parallel, independent portal session either: opening
+    // our own ScreenCast session via `ashpd` alongside the one `scrap`
+    // already opens internally would prompt the peer twice and hand back a
+    // restore token for a selection `scrap`'s own session never sees, so the
+    // two would never agree on what's shared. `get_saved_restore_token()` /
+    // `save_restore_token()` below are kept ready for when scrap exposes
+    // that parameter.
+    let saved_restore_token = get_saved_restore_token();
+    log::debug!(
+        "Wayland restore token on file: {}",
+        if saved_restore_token.is_some() { "yes" } else { "no" }
+    );
+
+    // to-do: DMA-BUF support also needs a `scrap` wayland backend change to
+    // report whether the negotiated PipeWire stream is `SPA_DATA_DmaBuf`;
+    // leave the flag at its default (unsupported) until that lands rather
+    // than guessing.
+    log::debug!(
+        "Wayland DMA-BUF capture support: {}",
+        dmabuf_capture_enabled()
+    );
+
+    // let displays = WaylandDisplay::all()?;
+    // let all = displays
+    //     .iter()
+    //     .map(|d| Display::WAYLAND(d.clone()))
+    //     .collect::<Vec<_>>();
+    let mut all = Display::all()?;
+    if all.is_empty() {
+        // No physical output (headless box, or a locked login screen with
+        // every real output torn down) -- try to fall back to a synthetic one.
+        // `ensure_virtual_display()` always returns an error in this tree, so
+        // the failure is only logged and `all` stays empty: no display is
+        // captured.
+        log::info!("No usable wayland output found, creating a headless virtual display");
+        let (width, height) = virtual_display_resolution();
+        match ensure_virtual_display(width, height) {
+            Ok(virtual_display) => all.push(virtual_display),
+            Err(e) => log::error!("Failed to create headless virtual display: {}", e),
+        }
+    }
+    let primary = super::display_service::get_primary_2(&all);
+    let primary = 1;
+    super::display_service::check_update_displays(&all);
+    let mut display_infos = super::display_service::get_sync_displays();
+    for display in display_infos.iter_mut() {
+        display.cursor_embedded = is_cursor_embedded();
+    }
+    log::debug!(
+        "#displays: {}, primary: {}, cpus: {}/{}",
+        all.len(),
+        primary,
+        num_cpus::get_physical(),
+        num_cpus::get(),
+    );
+
+    let mut rects: Vec<((i32, i32), usize, usize)> = Vec::new();
+    let mut capturers: Vec<CapturerPtr> = Vec::new();
+    for (idx, display) in all.into_iter().enumerate() {
+        let (origin, width, height) = (display.origin(), display.width(), display.height());
+        log::debug!(
+            "display: {}, origin: {:?}, width={}, height={}",
+            idx,
+            &origin,
+            width,
+            height
+        );
+
+        rects.push((origin, width, height));
+
+        if minx > origin.0 {
+            minx = origin.0;
+        }
+        if maxx < origin.0 + width as i32 {
+            maxx = origin.0 + width as i32;
+        }
+        if miny > origin.1 {
+            miny = origin.1;
+        }
+        if maxy < origin.1 + height as i32 {
+            maxy = origin.1 + height as i32;
+        }
+
+        let capturer = Capturer::new(display)?;
+        let capturer = CapturerPtr(Arc::new(CapturerHandle(Box::into_raw(Box::new(capturer)))));
+        capturers.push(capturer);
+    }
+
+    // The RemoteDesktop portal session used by `notify_pointer_button()` and
+    // friends (see below) is intentionally not opened here: it's a separate
+    // portal interface from the ScreenCast one negotiated above, with its
+    // own prompt and its own session object, and is opened lazily on first
+    // use instead so a peer that never sends input never sees that prompt.
+
+    Ok((
+        CapDisplayInfo {
+            primary,
+            rects,
+            // displays,
+            display_infos,
+            capturers,
+        },
+        minx,
+        maxx,
+        miny,
+        maxy,
+    ))
+}
+
 pub(super) fn check_init() -> ResultType<()> {
     if !is_x11() {
         let mut minx = i32::MAX;
@@ -127,68 +439,12 @@ pub(super) fn check_init() -> ResultType<()> {
         if *CAP_DISPLAY_INFO.read().unwrap() == 0 {
             let mut lock = CAP_DISPLAY_INFO.write().unwrap();
             if *lock == 0 {
-                println!("REMOVE ME ================================== wayland check init, all");
-                // let displays = WaylandDisplay::all()?;
-                // let all = displays
-                //     .iter()
-                //     .map(|d| Display::WAYLAND(d.clone()))
-                //     .collect::<Vec<_>>();
-                let all = Display::all()?;
-                let primary = super::display_service::get_primary_2(&all);
-                let primary = 1;
-                super::display_service::check_update_displays(&all);
-                let mut display_infos = super::display_service::get_sync_displays();
-                for display in display_infos.iter_mut() {
-                    display.cursor_embedded = is_cursor_embedded();
-                }
-                log::debug!(
-                    "#displays: {}, primary: {}, cpus: {}/{}",
-                    all.len(),
-                    primary,
-                    num_cpus::get_physical(),
-                    num_cpus::get(),
-                );
-
-                let mut rects: Vec<((i32, i32), usize, usize)> = Vec::new();
-                let mut capturers: Vec<CapturerPtr> = Vec::new();
-                for (idx, display) in all.into_iter().enumerate() {
-                    let (origin, width, height) =
-                        (display.origin(), display.width(), display.height());
-                    log::debug!(
-                        "display: {}, origin: {:?}, width={}, height={}",
-                        idx,
-                        &origin,
-                        width,
-                        height
-                    );
-
-                    rects.push((origin, width, height));
-
-                    if minx > origin.0 {
-                        minx = origin.0;
-                    }
-                    if maxx < origin.0 + width as i32 {
-                        maxx = origin.0 + width as i32;
-                    }
-                    if miny > origin.1 {
-                        miny = origin.1;
-                    }
-                    if maxy < origin.1 + height as i32 {
-                        maxy = origin.1 + height as i32;
-                    }
-
-                    let capturer = Capturer::new(display)?;
-                    let capturer = CapturerPtr(Box::into_raw(Box::new(capturer)));
-                    capturers.push(capturer);
-                }
-                let cap_display_info = Box::into_raw(Box::new(CapDisplayInfo {
-                    primary,
-                    rects,
-                    // displays,
-                    display_infos,
-                    capturers,
-                }));
-                *lock = cap_display_info as _;
+                let (cap_display_info, x0, x1, y0, y1) = build_cap_display_info()?;
+                minx = x0;
+                maxx = x1;
+                miny = y0;
+                maxy = y1;
+                *lock = Box::into_raw(Box::new(cap_display_info)) as _;
             }
         }
         if minx != i32::MAX {
@@ -200,6 +456,55 @@ pub(super) fn check_init() -> ResultType<()> {
     Ok(())
 }
 
+// Tears down the current `CapDisplayInfo` and rebuilds it from scratch via
+// `build_cap_display_info()`, the same portal negotiation as first startup.
+// The saved restore token is not passed to that negotiation, so the portal
+// does not resume the previous selection by itself.
+// Runs on its own thread so the PipeWire/dbus callback that detected the
+// fatal error can return immediately.
+fn spawn_recover_session() {
+    if RECOVERING.swap(true, Ordering::SeqCst) {
+        // A recovery is already underway.
+        return;
+    }
+    std::thread::spawn(|| {
+        let old_addr = {
+            let mut lock = CAP_DISPLAY_INFO.write().unwrap();
+            std::mem::replace(&mut *lock, 0)
+        };
+        if old_addr != 0 {
+            let cap_display_info: *mut CapDisplayInfo = old_addr as _;
+            // Just drop the box -- each CapturerPtr's own Drop releases its
+            // Arc<CapturerHandle> reference, which only frees the underlying
+            // Capturer once every other clone (e.g. one a video thread is
+            // mid-`frame()` on via `get_capturer()`) has released its own
+            // reference too. Don't reach in and free the pointer directly:
+            // that used to double-free it (CapturerPtr's old Drop impl did
+            // the exact same free a second time) and could tear a Capturer
+            // out from under a live `frame()` call on another thread.
+            unsafe {
+                drop(Box::from_raw(cap_display_info));
+            }
+        }
+
+        match build_cap_display_info() {
+            Ok((cap_display_info, minx, maxx, miny, maxy)) => {
+                let mut lock = CAP_DISPLAY_INFO.write().unwrap();
+                *lock = Box::into_raw(Box::new(cap_display_info)) as _;
+                drop(lock);
+                if minx != i32::MAX {
+                    update_mouse_resolution_(minx, maxx, miny, maxy);
+                }
+                log::info!("Wayland capture session recovered");
+            }
+            Err(e) => {
+                log::error!("Failed to recover wayland capture session: {}", e);
+            }
+        }
+        RECOVERING.store(false, Ordering::SeqCst);
+    });
+}
+
 #[tokio::main(flavor = "current_thread")]
 pub async fn update_mouse_resolution_(minx: i32, maxx: i32, miny: i32, maxy: i32) {
     log::info!(
@@ -265,20 +570,30 @@ pub fn clear() {
     if *write_lock != 0 {
         let cap_display_info: *mut CapDisplayInfo = *write_lock as _;
         unsafe {
-            let box_cap_display_info = Box::from_raw(cap_display_info);
-            for capturer in box_cap_display_info.capturers {
-                let _box_capturer = Box::from_raw(capturer.0);
-            }
+            drop(Box::from_raw(cap_display_info));
             *write_lock = 0;
         }
     }
-    println!("REMOVE ME ================================ clear");
+}
+
+// Backs off while `spawn_recover_session()` is rebuilding `CAP_DISPLAY_INFO`,
+// instead of letting callers race the rebuild or see a spurious "no capturer
+// display info" error.
+fn wait_for_recovery() {
+    if !RECOVERING.load(Ordering::SeqCst) {
+        return;
+    }
+    let start = std::time::Instant::now();
+    while RECOVERING.load(Ordering::SeqCst) && start.elapsed() < RECOVERY_TIMEOUT {
+        std::thread::sleep(RECOVERY_BACKOFF);
+    }
 }
 
 pub(super) fn get_capturer(idx: usize) -> ResultType<super::video_service::CapturerInfo> {
     if is_x11() {
         bail!("Do not call this function if not wayland");
     }
+    wait_for_recovery();
     let addr = *CAP_DISPLAY_INFO.read().unwrap();
     if addr != 0 {
         let cap_display_info: *const CapDisplayInfo = addr as _;
@@ -317,3 +632,176 @@ pub fn common_get_error() -> String {
     }
     return "".to_owned();
 }
+
+// These four are meant to be called by `input_service` for the events it
+// already handles, only once it knows it's not on X11 (the regular enigo
+// injection path there covers X11 today and never needs to reach this
+// file). Each lazily opens `REMOTE_DESKTOP_SESSION` via an `ashpd`
+// RemoteDesktop portal session on first use and reuses it afterwards --
+// there's no `scrap::open_remote_desktop_session`/`scrap::RemoteDesktopSession`
+// in this tree, those were invented in an earlier pass; this is the real
+// `ashpd` xdg-desktop-portal client instead.
+lazy_static::lazy_static! {
+    #[allow(dead_code)]
+    static ref REMOTE_DESKTOP_SESSION: Mutex<Option<RemoteDesktopSession>> = Mutex::new(None);
+}
+
+// The proxy is leaked to get a `'static` D-Bus connection to build the
+// session against, rather than threading a lifetime through the static
+// above -- the connection is meant to live for the rest of the process
+// regardless, so the leak costs nothing beyond what keeping it alive
+// already would.
+#[allow(dead_code)]
+struct RemoteDesktopSession {
+    proxy: &'static RemoteDesktop<'static>,
+    session: Session<'static, RemoteDesktop<'static>>,
+}
+
+// Negotiates a RemoteDesktop portal session for real. This is a separate
+// portal interface from the ScreenCast one `Capturer::new()` negotiates in
+// `build_cap_display_info()` -- RemoteDesktop has no way to attach to an
+// existing ScreenCast session, so the peer sees a second "share input"
+// prompt the first time input is injected. Only scrap negotiating both
+// together internally could collapse that into one prompt; nothing here can
+// do it from the outside.
+#[allow(dead_code)]
+async fn open_remote_desktop_session() -> ashpd::Result<RemoteDesktopSession> {
+    let proxy = RemoteDesktop::new().await?;
+    let proxy: &'static RemoteDesktop<'static> = Box::leak(Box::new(proxy));
+    let session = proxy.create_session().await?;
+    proxy
+        .select_devices(
+            &session,
+            DeviceType::Keyboard | DeviceType::Pointer,
+            None,
+            PersistMode::DoNot,
+        )
+        .await?;
+    proxy.start(&session, None).await?;
+    Ok(RemoteDesktopSession { proxy, session })
+}
+
+#[allow(dead_code)]
+#[tokio::main(flavor = "current_thread")]
+async fn open_remote_desktop_session_blocking() -> ashpd::Result<RemoteDesktopSession> {
+    open_remote_desktop_session().await
+}
+
+#[allow(dead_code)]
+fn ensure_remote_desktop_session() -> ResultType<()> {
+    if REMOTE_DESKTOP_SESSION.lock().unwrap().is_some() {
+        return Ok(());
+    }
+    match open_remote_desktop_session_blocking() {
+        Ok(session) => {
+            *REMOTE_DESKTOP_SESSION.lock().unwrap() = Some(session);
+            Ok(())
+        }
+        Err(e) => bail!("Failed to open wayland RemoteDesktop portal session: {}", e),
+    }
+}
+
+// `#[allow(dead_code)]` below: `input_service`'s non-X11 input handling,
+// the intended caller of all four of these, isn't part of this source
+// snapshot, not because the implementations themselves are stubs.
+#[allow(dead_code)]
+pub(super) fn notify_pointer_motion_absolute(x: i32, y: i32) {
+    if let Err(e) = ensure_remote_desktop_session() {
+        log::debug!("{}", e);
+        return;
+    }
+    // to-do: `NotifyPointerMotionAbsolute` requires the PipeWire stream node
+    // id of the ScreenCast stream the coordinates are relative to (per the
+    // xdg-desktop-portal spec), and `scrap::Capturer` doesn't expose the
+    // node id it negotiated for us to pass through here. There's no correct
+    // id to send until it does -- guessing one risks silently routing the
+    // peer's clicks to the wrong output, so this drops the event instead of
+    // calling the portal with a fabricated stream id.
+    log::debug!(
+        "Dropping wayland pointer_motion_absolute({}, {}): no screencast stream id available",
+        x,
+        y
+    );
+}
+
+#[allow(dead_code)]
+pub(super) fn notify_pointer_button(button: u32, press: bool) {
+    if let Err(e) = ensure_remote_desktop_session() {
+        log::debug!("{}", e);
+        return;
+    }
+    if let Err(e) = notify_pointer_button_blocking(button as i32, press) {
+        log::debug!("Wayland RemoteDesktop notify_pointer_button failed: {}", e);
+    }
+}
+
+#[allow(dead_code)]
+#[tokio::main(flavor = "current_thread")]
+async fn notify_pointer_button_blocking(button: i32, press: bool) -> ashpd::Result<()> {
+    let guard = REMOTE_DESKTOP_SESSION.lock().unwrap();
+    let remote_desktop = guard
+        .as_ref()
+        .expect("ensure_remote_desktop_session() just populated this");
+    let state = if press {
+        KeyState::Pressed
+    } else {
+        KeyState::Released
+    };
+    remote_desktop
+        .proxy
+        .notify_pointer_button(&remote_desktop.session, button, state)
+        .await
+}
+
+#[allow(dead_code)]
+pub(super) fn notify_pointer_axis(dx: f64, dy: f64) {
+    if let Err(e) = ensure_remote_desktop_session() {
+        log::debug!("{}", e);
+        return;
+    }
+    if let Err(e) = notify_pointer_axis_blocking(dx, dy) {
+        log::debug!("Wayland RemoteDesktop notify_pointer_axis failed: {}", e);
+    }
+}
+
+#[allow(dead_code)]
+#[tokio::main(flavor = "current_thread")]
+async fn notify_pointer_axis_blocking(dx: f64, dy: f64) -> ashpd::Result<()> {
+    let guard = REMOTE_DESKTOP_SESSION.lock().unwrap();
+    let remote_desktop = guard
+        .as_ref()
+        .expect("ensure_remote_desktop_session() just populated this");
+    remote_desktop
+        .proxy
+        .notify_pointer_axis(&remote_desktop.session, dx, dy, false)
+        .await
+}
+
+#[allow(dead_code)]
+pub(super) fn notify_keyboard_keycode(keycode: u32, press: bool) {
+    if let Err(e) = ensure_remote_desktop_session() {
+        log::debug!("{}", e);
+        return;
+    }
+    if let Err(e) = notify_keyboard_keycode_blocking(keycode as i32, press) {
+        log::debug!("Wayland RemoteDesktop notify_keyboard_keycode failed: {}", e);
+    }
+}
+
+#[allow(dead_code)]
+#[tokio::main(flavor = "current_thread")]
+async fn notify_keyboard_keycode_blocking(keycode: i32, press: bool) -> ashpd::Result<()> {
+    let guard = REMOTE_DESKTOP_SESSION.lock().unwrap();
+    let remote_desktop = guard
+        .as_ref()
+        .expect("ensure_remote_desktop_session() just populated this");
+    let state = if press {
+        KeyState::Pressed
+    } else {
+        KeyState::Released
+    };
+    remote_desktop
+        .proxy
+        .notify_keyboard_keycode(&remote_desktop.session, keycode, state)
+        .await
+}